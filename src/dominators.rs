@@ -0,0 +1,249 @@
+//! Dominator tree and dominance frontier computation, built directly on the
+//! basic blocks and CFG edges `Function` already exposes.
+//!
+//! Uses the Cooper-Harvey-Kennedy iterative algorithm: blocks are numbered in
+//! reverse postorder from the entry block, and `idom` is refined by
+//! repeatedly intersecting each block's processed predecessors until no
+//! entry changes.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::object::{BasicBlock, Function};
+
+pub struct Dominators {
+    pub entry: String,
+    pub rpo: Vec<String>,
+    pub idom: HashMap<String, String>,
+    pub frontier: HashMap<String, HashSet<String>>,
+    pub children: HashMap<String, Vec<String>>,
+}
+
+impl Dominators {
+    /// Returns whether block `a` dominates block `b` (every path from the
+    /// entry to `b` passes through `a`).
+    pub fn dominates(&self, a: &str, b: &str) -> bool {
+        if a == b {
+            return true;
+        }
+
+        let mut cur = b.to_string();
+        loop {
+            let parent = match self.idom.get(&cur) {
+                Some(p) => p,
+                None => return false,
+            };
+            if parent == &cur {
+                return false; // reached the entry without finding `a`
+            }
+            if parent == a {
+                return true;
+            }
+            cur = parent.clone();
+        }
+    }
+}
+
+impl Function {
+    /// Computes the dominator tree, dominance frontiers, and reverse
+    /// postorder numbering for this function's control-flow graph.
+    pub fn dominators(&self) -> Dominators {
+        let basic_blocks = self.get_basic_blocks();
+        let (successors, predecessors) = self.get_edges(&basic_blocks);
+
+        let entry = match basic_blocks.first() {
+            Some(b) => b.label.clone(),
+            None => {
+                return Dominators {
+                    entry: String::new(),
+                    rpo: vec![],
+                    idom: HashMap::new(),
+                    frontier: HashMap::new(),
+                    children: HashMap::new(),
+                }
+            }
+        };
+
+        let rpo = reverse_postorder(&entry, &successors);
+        let idom = compute_idom(&rpo, &predecessors);
+        let frontier = compute_frontier(&basic_blocks, &predecessors, &idom);
+        let children = compute_children(&idom);
+
+        Dominators { entry, rpo, idom, frontier, children }
+    }
+}
+
+fn reverse_postorder<'a>(entry: &'a str, successors: &HashMap<&'a str, Vec<&'a str>>) -> Vec<String> {
+    let mut visited = HashSet::new();
+    let mut postorder = vec![];
+    visit(entry, successors, &mut visited, &mut postorder);
+    postorder.reverse();
+    postorder
+}
+
+fn visit<'a>(
+    node: &'a str,
+    successors: &HashMap<&'a str, Vec<&'a str>>,
+    visited: &mut HashSet<&'a str>,
+    postorder: &mut Vec<String>,
+) {
+    if !visited.insert(node) {
+        return;
+    }
+    if let Some(succs) = successors.get(node) {
+        for succ in succs {
+            visit(succ, successors, visited, postorder);
+        }
+    }
+    postorder.push(node.to_string());
+}
+
+fn compute_idom(rpo: &[String], predecessors: &HashMap<&str, Vec<&str>>) -> HashMap<String, String> {
+    if rpo.is_empty() {
+        return HashMap::new();
+    }
+
+    let position: HashMap<&str, usize> = rpo.iter().enumerate().map(|(i, b)| (b.as_str(), i)).collect();
+    let entry = &rpo[0];
+
+    let mut idom: HashMap<String, String> = HashMap::new();
+    idom.insert(entry.clone(), entry.clone());
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for b in rpo.iter().skip(1) {
+            let preds = match predecessors.get(b.as_str()) {
+                Some(p) => p,
+                None => continue,
+            };
+
+            let processed: Vec<&str> = preds.iter().copied().filter(|p| idom.contains_key(*p)).collect();
+            let mut iter = processed.iter();
+            let mut new_idom = match iter.next() {
+                Some(p) => p.to_string(),
+                None => continue,
+            };
+
+            for &p in iter {
+                new_idom = intersect(p, &new_idom, &position, &idom);
+            }
+
+            if idom.get(b.as_str()) != Some(&new_idom) {
+                idom.insert(b.clone(), new_idom);
+                changed = true;
+            }
+        }
+    }
+
+    idom
+}
+
+fn intersect(a: &str, b: &str, position: &HashMap<&str, usize>, idom: &HashMap<String, String>) -> String {
+    let mut a = a.to_string();
+    let mut b = b.to_string();
+
+    while a != b {
+        while position[a.as_str()] > position[b.as_str()] {
+            a = idom[&a].clone();
+        }
+        while position[b.as_str()] > position[a.as_str()] {
+            b = idom[&b].clone();
+        }
+    }
+
+    a
+}
+
+fn compute_frontier(
+    basic_blocks: &[BasicBlock],
+    predecessors: &HashMap<&str, Vec<&str>>,
+    idom: &HashMap<String, String>,
+) -> HashMap<String, HashSet<String>> {
+    let mut frontier: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for b in basic_blocks {
+        let preds = match predecessors.get(b.label.as_str()) {
+            Some(p) if p.len() >= 2 => p,
+            _ => continue,
+        };
+
+        let idom_b = match idom.get(&b.label) {
+            Some(d) => d,
+            None => continue,
+        };
+
+        for &p in preds {
+            let mut runner = p.to_string();
+            while &runner != idom_b {
+                frontier.entry(runner.clone()).or_insert_with(HashSet::new).insert(b.label.clone());
+                runner = match idom.get(&runner) {
+                    Some(next) => next.clone(),
+                    None => break,
+                };
+            }
+        }
+    }
+
+    frontier
+}
+
+fn compute_children(idom: &HashMap<String, String>) -> HashMap<String, Vec<String>> {
+    let mut children: HashMap<String, Vec<String>> = HashMap::new();
+    for (node, parent) in idom {
+        if node != parent {
+            children.entry(parent.clone()).or_insert_with(Vec::new).push(node.clone());
+        }
+    }
+    // `idom` is a `HashMap`, so the order nodes are folded into each parent's
+    // list is otherwise unspecified; sort so the dominator-tree DFS (and
+    // anything else that walks `children`) visits siblings deterministically.
+    for siblings in children.values_mut() {
+        siblings.sort();
+    }
+    children
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // entry -> b -> d, entry -> c -> d (diamond), so `intersect` must walk
+    // `b` and `c` up to their common ancestor `entry` rather than stopping
+    // at the first shared position.
+    #[test]
+    fn intersect_finds_common_ancestor_in_a_diamond() {
+        let position: HashMap<&str, usize> =
+            [("entry", 0), ("b", 1), ("c", 2), ("d", 3)].into_iter().collect();
+        let idom: HashMap<String, String> = [
+            ("entry".to_string(), "entry".to_string()),
+            ("b".to_string(), "entry".to_string()),
+            ("c".to_string(), "entry".to_string()),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(intersect("b", "c", &position, &idom), "entry");
+    }
+
+    #[test]
+    fn dominators_walks_a_diamond_cfg() {
+        let src = "@main {\n\
+.entry:\n\
+  x: int = const 1;\n\
+  br x .left .right;\n\
+.left:\n\
+  jmp .join;\n\
+.right:\n\
+  jmp .join;\n\
+.join:\n\
+  ret;\n\
+}\n";
+        let program = crate::textual::parse_program(src).expect("parse");
+        let dom = program.functions[0].dominators();
+
+        assert!(dom.dominates(&dom.entry, "join"));
+        assert!(!dom.dominates("left", "join"));
+        assert!(dom.frontier.get("left").map(|f| f.contains("join")).unwrap_or(false));
+    }
+}
@@ -0,0 +1,498 @@
+//! Tree-walking interpreter that actually executes a `Program`.
+//!
+//! This turns the crate from a static analyzer over the Bril IR into a
+//! runnable one: `Program::run_main` resolves and calls `@main`, and
+//! `Function::run` executes a single function body against a program counter
+//! that follows `jmp`/`br` by resolving label positions ahead of time.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::object::{BigIntLiteral, Code, EffectOps, Function, Instruction, Literal, Program, ValueOps};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeError {
+    UndefinedVariable(String),
+    UndefinedLabel(String),
+    UndefinedFunction(String),
+    TypeMismatch(String),
+    DivisionByZero,
+    ArityMismatch { func: String, expected: usize, found: usize },
+    UseAfterFree(usize),
+    OutOfBounds { region: usize, offset: i64 },
+    MissingReturn(String),
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeError::UndefinedVariable(name) => write!(f, "undefined variable `{}`", name),
+            RuntimeError::UndefinedLabel(name) => write!(f, "undefined label `.{}`", name),
+            RuntimeError::UndefinedFunction(name) => write!(f, "undefined function `@{}`", name),
+            RuntimeError::TypeMismatch(msg) => write!(f, "type mismatch: {}", msg),
+            RuntimeError::DivisionByZero => write!(f, "division by zero"),
+            RuntimeError::ArityMismatch { func, expected, found } => write!(
+                f,
+                "`@{}` expects {} argument(s), found {}",
+                func, expected, found
+            ),
+            RuntimeError::UseAfterFree(region) => write!(f, "use of freed region {}", region),
+            RuntimeError::OutOfBounds { region, offset } => {
+                write!(f, "offset {} out of bounds for region {}", offset, region)
+            }
+            RuntimeError::MissingReturn(func) => {
+                write!(f, "`@{}` fell off the end without a `ret`", func)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+/// A runtime value: either a plain literal or a pointer into the heap.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Bool(bool),
+    Float(f64),
+    BigInt(BigIntLiteral),
+    Pointer { region: usize, offset: i64 },
+}
+
+impl Value {
+    fn as_int(&self) -> Result<i64, RuntimeError> {
+        match self {
+            Value::Int(n) => Ok(*n),
+            other => Err(RuntimeError::TypeMismatch(format!("expected int, found {:?}", other))),
+        }
+    }
+
+    fn as_bool(&self) -> Result<bool, RuntimeError> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            other => Err(RuntimeError::TypeMismatch(format!("expected bool, found {:?}", other))),
+        }
+    }
+
+    fn as_float(&self) -> Result<f64, RuntimeError> {
+        match self {
+            Value::Float(f) => Ok(*f),
+            other => Err(RuntimeError::TypeMismatch(format!("expected float, found {:?}", other))),
+        }
+    }
+
+    fn as_pointer(&self) -> Result<(usize, i64), RuntimeError> {
+        match self {
+            Value::Pointer { region, offset } => Ok((*region, *offset)),
+            other => Err(RuntimeError::TypeMismatch(format!("expected pointer, found {:?}", other))),
+        }
+    }
+
+    fn from_literal(lit: &Literal) -> Value {
+        match lit {
+            Literal::Int(n) => Value::Int(*n),
+            Literal::Bool(b) => Value::Bool(*b),
+            Literal::BigInt(n) => Value::BigInt(n.clone()),
+            Literal::Float(f) => Value::Float(*f),
+        }
+    }
+
+    fn into_literal(self) -> Result<Literal, RuntimeError> {
+        match self {
+            Value::Int(n) => Ok(Literal::Int(n)),
+            Value::Bool(b) => Ok(Literal::Bool(b)),
+            Value::BigInt(n) => Ok(Literal::BigInt(n)),
+            Value::Float(f) => Ok(Literal::Float(f)),
+            Value::Pointer { .. } => {
+                Err(RuntimeError::TypeMismatch("cannot return a pointer as a literal".into()))
+            }
+        }
+    }
+}
+
+/// The interpreter's heap: a set of allocated regions, indexed by the
+/// `region` half of a `Pointer`. A freed region is kept as `None` so later
+/// use produces a `UseAfterFree` error rather than silently reusing memory.
+struct Heap {
+    regions: Vec<Option<Vec<Value>>>,
+}
+
+impl Heap {
+    fn new() -> Heap {
+        Heap { regions: vec![] }
+    }
+
+    fn alloc(&mut self, size: i64) -> Value {
+        let region = self.regions.len();
+        self.regions.push(Some(vec![Value::Int(0); size.max(0) as usize]));
+        Value::Pointer { region, offset: 0 }
+    }
+
+    fn deref(&self, region: usize, offset: i64) -> Result<&Value, RuntimeError> {
+        let slot = self
+            .regions
+            .get(region)
+            .ok_or(RuntimeError::OutOfBounds { region, offset })?
+            .as_ref()
+            .ok_or(RuntimeError::UseAfterFree(region))?;
+        slot.get(offset as usize).ok_or(RuntimeError::OutOfBounds { region, offset })
+    }
+
+    fn store(&mut self, region: usize, offset: i64, value: Value) -> Result<(), RuntimeError> {
+        let slot = self
+            .regions
+            .get_mut(region)
+            .ok_or(RuntimeError::OutOfBounds { region, offset })?
+            .as_mut()
+            .ok_or(RuntimeError::UseAfterFree(region))?;
+        let cell = slot.get_mut(offset as usize).ok_or(RuntimeError::OutOfBounds { region, offset })?;
+        *cell = value;
+        Ok(())
+    }
+
+    fn free(&mut self, region: usize) -> Result<(), RuntimeError> {
+        let slot = self.regions.get_mut(region).ok_or(RuntimeError::OutOfBounds { region, offset: 0 })?;
+        if slot.is_none() {
+            return Err(RuntimeError::UseAfterFree(region));
+        }
+        *slot = None;
+        Ok(())
+    }
+}
+
+/// Builds label -> instruction-index lookup for jump targets within a
+/// function's flat `instrs` list.
+fn label_positions(instrs: &[Code]) -> HashMap<&str, usize> {
+    let mut positions = HashMap::new();
+    for (i, code) in instrs.iter().enumerate() {
+        if let Code::Label { label } = code {
+            positions.insert(label.as_str(), i);
+        }
+    }
+    positions
+}
+
+enum Flow {
+    Next,
+    Jump(usize),
+    Return(Option<Value>),
+}
+
+struct Interpreter<'a> {
+    program: &'a Program,
+    heap: Heap,
+}
+
+impl<'a> Interpreter<'a> {
+    fn call(&mut self, name: &str, args: Vec<Value>) -> Result<Option<Value>, RuntimeError> {
+        let func = self
+            .program
+            .functions
+            .iter()
+            .find(|f| f.name == name)
+            .ok_or_else(|| RuntimeError::UndefinedFunction(name.to_string()))?;
+
+        if func.args.len() != args.len() {
+            return Err(RuntimeError::ArityMismatch {
+                func: name.to_string(),
+                expected: func.args.len(),
+                found: args.len(),
+            });
+        }
+
+        let mut env: HashMap<String, Value> = HashMap::new();
+        for (arg, value) in func.args.iter().zip(args) {
+            env.insert(arg.name.clone(), value);
+        }
+
+        self.run_function(func, env)
+    }
+
+    fn run_function(
+        &mut self,
+        func: &Function,
+        mut env: HashMap<String, Value>,
+    ) -> Result<Option<Value>, RuntimeError> {
+        let labels = label_positions(&func.instrs);
+        let mut pc = 0;
+
+        // Tracks the label of the block we're currently in, and the label of
+        // the block we most recently jumped from, so `phi` can tell which
+        // incoming edge was taken.
+        let mut current_label = "entry".to_string();
+        let mut incoming_label = "entry".to_string();
+
+        while pc < func.instrs.len() {
+            if let Code::Label { label } = &func.instrs[pc] {
+                incoming_label = current_label.clone();
+                current_label = label.clone();
+            }
+
+            let flow = match &func.instrs[pc] {
+                Code::Label { .. } => Flow::Next,
+                Code::Instruction(instr) => self.step(instr, &mut env, &labels, &incoming_label)?,
+            };
+
+            match flow {
+                Flow::Next => pc += 1,
+                Flow::Jump(target) => pc = target,
+                Flow::Return(value) => return Ok(value),
+            }
+        }
+
+        // Falling off the end without a `ret` is only an error if the
+        // function actually promised a value; a void function (most
+        // commonly `@main`) legitimately ends this way.
+        if func.return_type.is_some() {
+            Err(RuntimeError::MissingReturn(func.name.clone()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn lookup(&self, env: &HashMap<String, Value>, name: &str) -> Result<Value, RuntimeError> {
+        env.get(name).cloned().ok_or_else(|| RuntimeError::UndefinedVariable(name.to_string()))
+    }
+
+    fn step(
+        &mut self,
+        instr: &Instruction,
+        env: &mut HashMap<String, Value>,
+        labels: &HashMap<&str, usize>,
+        incoming_label: &str,
+    ) -> Result<Flow, RuntimeError> {
+        match instr {
+            Instruction::Constant { dest, value, .. } => {
+                env.insert(dest.clone(), Value::from_literal(value));
+                Ok(Flow::Next)
+            }
+
+            Instruction::Value { op, dest, args, funcs, labels: instr_labels, .. } => {
+                let result = self.eval_value_op(op, args, funcs, instr_labels, incoming_label, env)?;
+                if let Some(dest) = dest {
+                    if let Some(value) = result {
+                        env.insert(dest.clone(), value);
+                    }
+                }
+                Ok(Flow::Next)
+            }
+
+            Instruction::Effect { op, args, labels: instr_labels, .. } => match op {
+                EffectOps::Jmp => {
+                    let target = instr_labels
+                        .first()
+                        .ok_or_else(|| RuntimeError::TypeMismatch("jmp requires a label".into()))?;
+                    let pos = labels
+                        .get(target.as_str())
+                        .ok_or_else(|| RuntimeError::UndefinedLabel(target.clone()))?;
+                    Ok(Flow::Jump(*pos))
+                }
+                EffectOps::Br => {
+                    let cond = self.lookup(env, &args[0])?.as_bool()?;
+                    let target = if cond { &instr_labels[0] } else { &instr_labels[1] };
+                    let pos = labels
+                        .get(target.as_str())
+                        .ok_or_else(|| RuntimeError::UndefinedLabel(target.clone()))?;
+                    Ok(Flow::Jump(*pos))
+                }
+                EffectOps::Ret => {
+                    let value = match args.first() {
+                        Some(name) => Some(self.lookup(env, name)?),
+                        None => None,
+                    };
+                    Ok(Flow::Return(value))
+                }
+                EffectOps::Print => {
+                    let values: Result<Vec<Value>, RuntimeError> =
+                        args.iter().map(|a| self.lookup(env, a)).collect();
+                    let rendered: Vec<String> = values?.iter().map(render_value).collect();
+                    println!("{}", rendered.join(" "));
+                    Ok(Flow::Next)
+                }
+                EffectOps::Nop => Ok(Flow::Next),
+                EffectOps::Free => {
+                    let (region, _) = self.lookup(env, &args[0])?.as_pointer()?;
+                    self.heap.free(region)?;
+                    Ok(Flow::Next)
+                }
+                EffectOps::Store => {
+                    let (region, offset) = self.lookup(env, &args[0])?.as_pointer()?;
+                    let value = self.lookup(env, &args[1])?;
+                    self.heap.store(region, offset, value)?;
+                    Ok(Flow::Next)
+                }
+            },
+        }
+    }
+
+    fn eval_value_op(
+        &mut self,
+        op: &ValueOps,
+        args: &[String],
+        funcs: &[String],
+        labels: &[String],
+        incoming_label: &str,
+        env: &HashMap<String, Value>,
+    ) -> Result<Option<Value>, RuntimeError> {
+        let arg = |i: usize| self.lookup(env, &args[i]);
+
+        let value = match op {
+            ValueOps::Add => Value::Int(arg(0)?.as_int()?.wrapping_add(arg(1)?.as_int()?)),
+            ValueOps::Mul => Value::Int(arg(0)?.as_int()?.wrapping_mul(arg(1)?.as_int()?)),
+            ValueOps::Sub => Value::Int(arg(0)?.as_int()?.wrapping_sub(arg(1)?.as_int()?)),
+            ValueOps::Div => {
+                let denom = arg(1)?.as_int()?;
+                if denom == 0 {
+                    return Err(RuntimeError::DivisionByZero);
+                }
+                // `wrapping_div`, not `/`, so `i64::MIN / -1` wraps back to
+                // `i64::MIN` instead of panicking, matching Add/Mul/Sub above.
+                Value::Int(arg(0)?.as_int()?.wrapping_div(denom))
+            }
+            ValueOps::Eq => Value::Bool(arg(0)?.as_int()? == arg(1)?.as_int()?),
+            ValueOps::Lt => Value::Bool(arg(0)?.as_int()? < arg(1)?.as_int()?),
+            ValueOps::Gt => Value::Bool(arg(0)?.as_int()? > arg(1)?.as_int()?),
+            ValueOps::Le => Value::Bool(arg(0)?.as_int()? <= arg(1)?.as_int()?),
+            ValueOps::Ge => Value::Bool(arg(0)?.as_int()? >= arg(1)?.as_int()?),
+            ValueOps::Fadd => Value::Float(arg(0)?.as_float()? + arg(1)?.as_float()?),
+            ValueOps::Fmul => Value::Float(arg(0)?.as_float()? * arg(1)?.as_float()?),
+            ValueOps::Fsub => Value::Float(arg(0)?.as_float()? - arg(1)?.as_float()?),
+            ValueOps::Fdiv => Value::Float(arg(0)?.as_float()? / arg(1)?.as_float()?),
+            ValueOps::Feq => Value::Bool(arg(0)?.as_float()? == arg(1)?.as_float()?),
+            ValueOps::Flt => Value::Bool(arg(0)?.as_float()? < arg(1)?.as_float()?),
+            ValueOps::Fgt => Value::Bool(arg(0)?.as_float()? > arg(1)?.as_float()?),
+            ValueOps::Fle => Value::Bool(arg(0)?.as_float()? <= arg(1)?.as_float()?),
+            ValueOps::Fge => Value::Bool(arg(0)?.as_float()? >= arg(1)?.as_float()?),
+            ValueOps::Not => Value::Bool(!arg(0)?.as_bool()?),
+            ValueOps::And => Value::Bool(arg(0)?.as_bool()? && arg(1)?.as_bool()?),
+            ValueOps::Or => Value::Bool(arg(0)?.as_bool()? || arg(1)?.as_bool()?),
+            ValueOps::Id => arg(0)?,
+            ValueOps::Alloc => {
+                let size = arg(0)?.as_int()?;
+                self.heap.alloc(size)
+            }
+            ValueOps::Load => {
+                let (region, offset) = arg(0)?.as_pointer()?;
+                self.heap.deref(region, offset)?.clone()
+            }
+            ValueOps::PtrAdd => {
+                let (region, offset) = arg(0)?.as_pointer()?;
+                let delta = arg(1)?.as_int()?;
+                Value::Pointer { region, offset: offset + delta }
+            }
+            ValueOps::Call => {
+                let name = funcs
+                    .first()
+                    .ok_or_else(|| RuntimeError::TypeMismatch("call requires a function".into()))?;
+                let call_args: Result<Vec<Value>, RuntimeError> =
+                    args.iter().map(|a| self.lookup(env, a)).collect();
+                return self.call(name, call_args?);
+            }
+            ValueOps::Phi => {
+                let source = labels
+                    .iter()
+                    .position(|l| l == incoming_label)
+                    .ok_or_else(|| {
+                        RuntimeError::TypeMismatch(format!(
+                            "phi has no entry for incoming block `.{}`",
+                            incoming_label
+                        ))
+                    })?;
+                let name = args.get(source).ok_or_else(|| {
+                    RuntimeError::TypeMismatch("phi has mismatched args/labels".into())
+                })?;
+                self.lookup(env, name)?
+            }
+        };
+
+        Ok(Some(value))
+    }
+}
+
+fn render_value(value: &Value) -> String {
+    match value {
+        Value::Int(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::BigInt(n) => n.0.clone(),
+        Value::Pointer { region, offset } => format!("ptr<{},{}>", region, offset),
+    }
+}
+
+impl Function {
+    /// Executes this function with the given arguments, returning its
+    /// result (if any) as a `Literal`.
+    pub fn run(&self, program: &Program, args: &[Literal]) -> Result<Option<Literal>, RuntimeError> {
+        let mut interp = Interpreter { program, heap: Heap::new() };
+        let env: HashMap<String, Value> = self
+            .args
+            .iter()
+            .zip(args.iter())
+            .map(|(arg, lit)| (arg.name.clone(), Value::from_literal(lit)))
+            .collect();
+
+        let result = interp.run_function(self, env)?;
+        result.map(Value::into_literal).transpose()
+    }
+}
+
+impl Program {
+    /// Resolves `@main` and runs it with no arguments, the typical entry
+    /// point for a standalone Bril program.
+    pub fn run_main(&self) -> Result<Option<Literal>, RuntimeError> {
+        let main = self
+            .functions
+            .iter()
+            .find(|f| f.name == "main")
+            .ok_or_else(|| RuntimeError::UndefinedFunction("main".to_string()))?;
+        main.run(self, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::textual::parse_program;
+
+    #[test]
+    fn div_by_min_int_and_neg_one_wraps_instead_of_panicking() {
+        let program = parse_program(
+            "@main {\n  a: int = const -9223372036854775808;\n  b: int = const -1;\n  c: int = div a b;\n  ret c;\n}\n",
+        )
+        .expect("parse");
+        let result = program.run_main().expect("run");
+        assert_eq!(result, Some(Literal::Int(i64::MIN)));
+    }
+
+    #[test]
+    fn div_by_zero_is_a_runtime_error() {
+        let program = parse_program(
+            "@main {\n  a: int = const 1;\n  b: int = const 0;\n  c: int = div a b;\n  ret c;\n}\n",
+        )
+        .expect("parse");
+        assert_eq!(program.run_main(), Err(RuntimeError::DivisionByZero));
+    }
+
+    #[test]
+    fn use_after_free_is_a_runtime_error() {
+        let program = parse_program(
+            "@main {\n  n: int = const 1;\n  p: ptr<int> = alloc n;\n  free p;\n  v: int = load p;\n  ret v;\n}\n",
+        )
+        .expect("parse");
+        assert_eq!(program.run_main(), Err(RuntimeError::UseAfterFree(0)));
+    }
+
+    #[test]
+    fn falling_off_the_end_of_a_void_function_is_not_an_error() {
+        let program =
+            parse_program("@main {\n  x: int = const 5;\n  print x;\n}\n").expect("parse");
+        assert_eq!(program.run_main(), Ok(None));
+    }
+
+    #[test]
+    fn falling_off_the_end_of_a_value_function_is_a_runtime_error() {
+        let program = parse_program("@main {\n  call @helper;\n  ret;\n}\n\n@helper: int {\n  x: int = const 5;\n}\n")
+            .expect("parse");
+        assert_eq!(program.run_main(), Err(RuntimeError::MissingReturn("helper".to_string())));
+    }
+}
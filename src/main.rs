@@ -1,28 +1,78 @@
-use serde_json::Result;
 use std::fs;
 use structopt::StructOpt;
 
+mod dominators;
+mod interp;
 mod object;
+mod ssa;
+mod textual;
+mod typecheck;
 use object::*;
 
 #[derive(StructOpt)]
 struct Cli {
     #[structopt(parse(from_os_str))]
     path: std::path::PathBuf,
+
+    /// Syntax of the input file: `json` or `text`
+    #[structopt(long, default_value = "json")]
+    format: String,
+
+    /// Syntax to print the parsed program back out as: `json`, `text`, or
+    /// `ssa` (rewrites every function into SSA form before printing as text)
+    #[structopt(long, default_value = "json")]
+    emit: String,
+
+    /// Execute `@main` with the tree-walking interpreter after typechecking
+    #[structopt(long)]
+    run: bool,
 }
 
 fn main() {
     let args = Cli::from_args();
     let data = fs::read_to_string(&args.path).expect("Unable to read file");
 
-    let deserialized: Result<Program> = serde_json::from_str(&data);
-    match deserialized {
+    let parsed: std::result::Result<Program, String> = match args.format.as_str() {
+        "text" => textual::parse_program(&data).map_err(|e| e.to_string()),
+        _ => serde_json::from_str(&data).map_err(|e| e.to_string()),
+    };
+
+    match parsed {
         Ok(p) => {
+            if let Err(errors) = p.typecheck() {
+                for e in &errors {
+                    eprintln!("type error: {}", e);
+                }
+                return;
+            }
+
             for f in &p.functions {
                 let basic_blocks = f.get_basic_blocks();
                 let (successors, _) = f.get_edges(&basic_blocks);
                 println!("add count: {}", f.count_add_ops());
                 f.cfg_dot(&basic_blocks, &successors);
+
+                let dom = f.dominators();
+                println!("dominator frontier for @{}: {:?}", f.name, dom.frontier);
+            }
+
+            if args.run {
+                match p.run_main() {
+                    Ok(value) => println!("result: {:?}", value),
+                    Err(e) => println!("runtime error: {}", e),
+                }
+            }
+
+            match args.emit.as_str() {
+                "text" => println!("{}", textual::print_program(&p)),
+                "ssa" => {
+                    let ssa = Program { functions: p.functions.iter().map(|f| f.to_ssa()).collect() };
+                    println!("{}", textual::print_program(&ssa));
+                }
+                _ => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&p).expect("serialize program")
+                ),
             }
         }
         Err(e) => println!("{:?}", e),
@@ -37,6 +37,7 @@ pub struct Arg {
 pub enum Type {
     Int,
     Bool,
+    Float,
     Ptr(Box<Type>),
 }
 
@@ -94,11 +95,134 @@ pub enum Instruction {
     },
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+/// An arbitrary-precision integer literal, too wide for `i64`. JSON can't
+/// carry bignums natively, so on the wire (and in text syntax) it's just the
+/// decimal digits, kept as a string rather than evaluated.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigIntLiteral(pub String);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum Literal {
     Bool(bool),
     Int(i64),
+    BigInt(#[serde(with = "bigint_wire")] BigIntLiteral),
+    Float(#[serde(with = "float_wire")] f64),
+}
+
+// f64 has no total ordering (NaN != NaN), so it can't participate in a
+// derived Eq/Hash. Compare and hash by bit pattern instead, which treats
+// every NaN payload as equal to itself -- good enough for "is this the same
+// literal", which is all Program's derived Eq/Hash need.
+impl PartialEq for Literal {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Literal::Bool(a), Literal::Bool(b)) => a == b,
+            (Literal::Int(a), Literal::Int(b)) => a == b,
+            (Literal::BigInt(a), Literal::BigInt(b)) => a == b,
+            (Literal::Float(a), Literal::Float(b)) => a.to_bits() == b.to_bits(),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Literal {}
+
+impl std::hash::Hash for Literal {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        match self {
+            Literal::Bool(b) => {
+                0u8.hash(state);
+                b.hash(state);
+            }
+            Literal::Int(n) => {
+                1u8.hash(state);
+                n.hash(state);
+            }
+            Literal::BigInt(n) => {
+                2u8.hash(state);
+                n.hash(state);
+            }
+            Literal::Float(f) => {
+                3u8.hash(state);
+                f.to_bits().hash(state);
+            }
+        }
+    }
+}
+
+/// On-wire encoding for `Literal::Float`: a decimal string (always carrying a
+/// `.` or exponent so it can't be confused with `BigInt`'s digit-only
+/// strings), with `NaN`/`Infinity`/`-Infinity` spelled out so those survive a
+/// JSON round-trip.
+///
+/// `format_float`/`parse_float` are `pub(crate)` so the textual syntax printer
+/// and lexer can reuse this exact encoding instead of drifting from it.
+pub(crate) mod float_wire {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        format_float(*value).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse_float(&text).map_err(serde::de::Error::custom)
+    }
+
+    pub(crate) fn format_float(x: f64) -> String {
+        if x.is_nan() {
+            "NaN".to_string()
+        } else if x.is_infinite() {
+            if x.is_sign_positive() { "Infinity".to_string() } else { "-Infinity".to_string() }
+        } else {
+            let text = x.to_string();
+            if text.contains('.') || text.contains('e') || text.contains('E') {
+                text
+            } else {
+                format!("{}.0", text)
+            }
+        }
+    }
+
+    pub(crate) fn parse_float(text: &str) -> Result<f64, String> {
+        match text {
+            "NaN" => Ok(f64::NAN),
+            "Infinity" => Ok(f64::INFINITY),
+            "-Infinity" => Ok(f64::NEG_INFINITY),
+            _ if text.contains('.') || text.contains('e') || text.contains('E') => {
+                text.parse::<f64>().map_err(|e| e.to_string())
+            }
+            _ => Err(format!("`{}` does not look like a float literal", text)),
+        }
+    }
+}
+
+/// On-wire encoding for `Literal::BigInt`: plain decimal digits (optionally
+/// signed), distinguished from `Float`'s strings by never containing a `.`
+/// or exponent.
+mod bigint_wire {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::BigIntLiteral;
+
+    pub fn serialize<S: Serializer>(value: &BigIntLiteral, serializer: S) -> Result<S::Ok, S::Error> {
+        value.0.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigIntLiteral, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        if is_decimal_integer(&text) {
+            Ok(BigIntLiteral(text))
+        } else {
+            Err(serde::de::Error::custom(format!("`{}` is not a valid big integer literal", text)))
+        }
+    }
+
+    fn is_decimal_integer(text: &str) -> bool {
+        let digits = text.strip_prefix('-').unwrap_or(text);
+        !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
@@ -133,6 +257,18 @@ pub enum ValueOps {
     Alloc,
     Load,
     PtrAdd,
+    // SSA
+    Phi,
+    // Floating point
+    Fadd,
+    Fmul,
+    Fsub,
+    Fdiv,
+    Feq,
+    Flt,
+    Fle,
+    Fgt,
+    Fge,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
@@ -0,0 +1,367 @@
+//! Rewrites a `Function` into SSA form: one `phi` per variable at each block
+//! in the iterated dominance frontier of its definitions, then variables
+//! renamed via a dominator-tree DFS with per-variable version stacks (the
+//! standard Cytron et al. construction). Phi placement does not consider
+//! liveness: a variable that is dead at a join still gets a `phi` there.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::dominators::Dominators;
+use crate::object::{Code, Function, Instruction, Type, ValueOps};
+
+/// A `phi` placed in some block, keyed by the original (pre-SSA) variable
+/// name it replaces. Filled in with one `(predecessor, value)` pair per
+/// incoming edge as the renaming DFS visits that predecessor; an edge along
+/// which the variable has no definition yet gets no pair.
+struct PhiSlot {
+    dest: String,
+    var_type: Type,
+    incoming: Vec<(String, String)>,
+}
+
+impl Function {
+    /// Returns an equivalent function rewritten into SSA form.
+    pub fn to_ssa(&self) -> Function {
+        let dom = self.dominators();
+        let basic_blocks = self.get_basic_blocks();
+        let (successors, _) = self.get_edges(&basic_blocks);
+
+        let mut var_types: HashMap<String, Type> = HashMap::new();
+        let mut defs: HashMap<String, HashSet<String>> = HashMap::new();
+
+        for arg in &self.args {
+            var_types.insert(arg.name.clone(), arg.arg_type.clone());
+            defs.entry(arg.name.clone()).or_default().insert(dom.entry.clone());
+        }
+
+        for bb in &basic_blocks {
+            for code in &bb.instrs {
+                if let Some((dest, dest_type)) = defined_var(code) {
+                    var_types.insert(dest.clone(), dest_type.clone());
+                    defs.entry(dest.clone()).or_default().insert(bb.label.clone());
+                }
+            }
+        }
+
+        let phi_vars = place_phis(&dom, &defs);
+
+        // Every `(block, var)` phi slot is created up front, before the
+        // rename DFS visits a single block. A predecessor can be visited
+        // before its successor in dominator-tree order (a loop header's
+        // incoming edge from outside the loop, a diamond sibling, ...), and
+        // if the successor's slot didn't exist yet that predecessor's
+        // incoming value would have nowhere to go. `dest` is overwritten
+        // with the real SSA name once the DFS actually reaches that block.
+        let mut phi_slots: HashMap<(String, String), PhiSlot> = HashMap::new();
+        for (block, vars) in &phi_vars {
+            for var in vars {
+                phi_slots.insert(
+                    (block.clone(), var.clone()),
+                    PhiSlot { dest: var.clone(), var_type: var_types[var].clone(), incoming: vec![] },
+                );
+            }
+        }
+
+        let mut counters: HashMap<String, u32> = HashMap::new();
+        let mut stacks: HashMap<String, Vec<String>> = HashMap::new();
+        let mut renamed: HashMap<String, Vec<Code>> = HashMap::new();
+
+        rename_block(
+            &dom.entry,
+            &dom,
+            &basic_blocks,
+            &successors,
+            &phi_vars,
+            &mut phi_slots,
+            &mut counters,
+            &mut stacks,
+            &mut renamed,
+        );
+
+        let mut instrs = vec![];
+        for bb in &basic_blocks {
+            instrs.push(Code::Label { label: bb.label.clone() });
+
+            let mut vars: Vec<&String> = phi_vars.get(&bb.label).into_iter().flatten().collect();
+            vars.sort();
+            for var in vars {
+                if let Some(slot) = phi_slots.get(&(bb.label.clone(), var.clone())) {
+                    let mut incoming = slot.incoming.clone();
+                    incoming.sort();
+                    let args = incoming.iter().map(|(_, v)| v.clone()).collect();
+                    let labels = incoming.iter().map(|(l, _)| l.clone()).collect();
+                    instrs.push(Code::Instruction(Instruction::Value {
+                        op: ValueOps::Phi,
+                        dest: Some(slot.dest.clone()),
+                        dest_type: Some(slot.var_type.clone()),
+                        args,
+                        funcs: vec![],
+                        labels,
+                    }));
+                }
+            }
+
+            if let Some(body) = renamed.remove(&bb.label) {
+                instrs.extend(body);
+            }
+        }
+
+        Function { name: self.name.clone(), args: self.args.clone(), return_type: self.return_type.clone(), instrs }
+    }
+}
+
+fn defined_var(code: &Code) -> Option<(&String, &Type)> {
+    match code {
+        Code::Instruction(Instruction::Constant { dest, dest_type, .. }) => Some((dest, dest_type)),
+        Code::Instruction(Instruction::Value { dest: Some(d), dest_type: Some(t), .. }) => Some((d, t)),
+        _ => None,
+    }
+}
+
+/// Classic iterated-dominance-frontier phi placement: for each variable,
+/// push its defining blocks onto a worklist and flood through dominance
+/// frontiers, placing (and re-triggering from) one phi per block reached.
+fn place_phis(dom: &Dominators, defs: &HashMap<String, HashSet<String>>) -> HashMap<String, HashSet<String>> {
+    let mut phi_vars: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for (var, def_blocks) in defs {
+        let mut worklist: Vec<String> = def_blocks.iter().cloned().collect();
+        let mut has_phi: HashSet<String> = HashSet::new();
+        let mut queued: HashSet<String> = def_blocks.iter().cloned().collect();
+
+        while let Some(b) = worklist.pop() {
+            let frontier = match dom.frontier.get(&b) {
+                Some(f) => f,
+                None => continue,
+            };
+
+            for d in frontier {
+                if has_phi.insert(d.clone()) {
+                    phi_vars.entry(d.clone()).or_default().insert(var.clone());
+                    if queued.insert(d.clone()) {
+                        worklist.push(d.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    phi_vars
+}
+
+fn fresh_name(var: &str, counters: &mut HashMap<String, u32>) -> String {
+    let count = counters.entry(var.to_string()).or_insert(0);
+    let name = if *count == 0 { var.to_string() } else { format!("{}_{}", var, count) };
+    *count += 1;
+    name
+}
+
+#[allow(clippy::too_many_arguments)]
+fn rename_block(
+    label: &str,
+    dom: &Dominators,
+    basic_blocks: &[crate::object::BasicBlock],
+    successors: &HashMap<&str, Vec<&str>>,
+    phi_vars: &HashMap<String, HashSet<String>>,
+    phi_slots: &mut HashMap<(String, String), PhiSlot>,
+    counters: &mut HashMap<String, u32>,
+    stacks: &mut HashMap<String, Vec<String>>,
+    renamed: &mut HashMap<String, Vec<Code>>,
+) {
+    let mut pushed = vec![];
+
+    if let Some(vars) = phi_vars.get(label) {
+        for var in vars {
+            let new_name = fresh_name(var, counters);
+            stacks.entry(var.clone()).or_default().push(new_name.clone());
+            pushed.push(var.clone());
+            // The slot itself was pre-created in `to_ssa`; just give it its
+            // real SSA name now that we've actually reached this block.
+            phi_slots.get_mut(&(label.to_string(), var.clone())).expect("phi slot pre-created").dest = new_name;
+        }
+    }
+
+    let mut body = vec![];
+    if let Some(bb) = basic_blocks.iter().find(|bb| bb.label == label) {
+        for code in &bb.instrs {
+            body.push(rename_code(code, &mut pushed, counters, stacks));
+        }
+    }
+    renamed.insert(label.to_string(), body);
+
+    if let Some(succs) = successors.get(label) {
+        for succ in succs {
+            if let Some(vars) = phi_vars.get(*succ) {
+                for var in vars {
+                    if let Some(value) = stacks.get(var).and_then(|s| s.last()) {
+                        if let Some(slot) = phi_slots.get_mut(&(succ.to_string(), var.clone())) {
+                            slot.incoming.push((label.to_string(), value.clone()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(children) = dom.children.get(label) {
+        for child in children {
+            rename_block(child, dom, basic_blocks, successors, phi_vars, phi_slots, counters, stacks, renamed);
+        }
+    }
+
+    for var in pushed {
+        if let Some(stack) = stacks.get_mut(&var) {
+            stack.pop();
+        }
+    }
+}
+
+fn rename_code(
+    code: &Code,
+    pushed: &mut Vec<String>,
+    counters: &mut HashMap<String, u32>,
+    stacks: &mut HashMap<String, Vec<String>>,
+) -> Code {
+    let current = |name: &str, stacks: &HashMap<String, Vec<String>>| -> String {
+        stacks.get(name).and_then(|s| s.last()).cloned().unwrap_or_else(|| name.to_string())
+    };
+
+    match code {
+        Code::Label { label } => Code::Label { label: label.clone() },
+        Code::Instruction(Instruction::Constant { op, dest, dest_type, value }) => {
+            let new_dest = fresh_name(dest, counters);
+            stacks.entry(dest.clone()).or_default().push(new_dest.clone());
+            pushed.push(dest.clone());
+            Code::Instruction(Instruction::Constant {
+                op: op.clone(),
+                dest: new_dest,
+                dest_type: dest_type.clone(),
+                value: value.clone(),
+            })
+        }
+        Code::Instruction(Instruction::Value { op, dest, dest_type, args, funcs, labels }) => {
+            let new_args = args.iter().map(|a| current(a, stacks)).collect();
+            let new_dest = dest.as_ref().map(|d| {
+                let new_name = fresh_name(d, counters);
+                stacks.entry(d.clone()).or_default().push(new_name.clone());
+                pushed.push(d.clone());
+                new_name
+            });
+            Code::Instruction(Instruction::Value {
+                op: op.clone(),
+                dest: new_dest,
+                dest_type: dest_type.clone(),
+                args: new_args,
+                funcs: funcs.clone(),
+                labels: labels.clone(),
+            })
+        }
+        Code::Instruction(Instruction::Effect { op, args, funcs, labels }) => {
+            let new_args = args.iter().map(|a| current(a, stacks)).collect();
+            Code::Instruction(Instruction::Effect {
+                op: op.clone(),
+                args: new_args,
+                funcs: funcs.clone(),
+                labels: labels.clone(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diamond_merge_gets_a_phi() {
+        let src = "@main {\n\
+.entry:\n\
+  c: bool = const true;\n\
+  br c .left .right;\n\
+.left:\n\
+  x: int = const 1;\n\
+  jmp .join;\n\
+.right:\n\
+  x: int = const 2;\n\
+  jmp .join;\n\
+.join:\n\
+  ret;\n\
+}\n";
+        let program = crate::textual::parse_program(src).expect("parse");
+        let ssa = program.functions[0].to_ssa();
+
+        let join_phis: Vec<_> = ssa
+            .instrs
+            .iter()
+            .skip_while(|c| !matches!(c, Code::Label { label } if label == "join"))
+            .filter(|c| matches!(c, Code::Instruction(Instruction::Value { op: ValueOps::Phi, .. })))
+            .collect();
+        assert_eq!(join_phis.len(), 1);
+
+        // Both branches' definitions must survive into the phi, regardless of
+        // which order the dominator tree happens to visit `left` and `right`.
+        match join_phis[0] {
+            Code::Instruction(Instruction::Value { labels, args, .. }) => {
+                let mut labels = labels.clone();
+                labels.sort();
+                assert_eq!(labels, vec!["left".to_string(), "right".to_string()]);
+                assert_eq!(args.len(), 2);
+            }
+            other => panic!("expected a phi instruction, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn loop_header_phi_gets_the_entry_incoming_edge() {
+        // entry -> loop, loop -> body -> loop, loop -> done. The `entry ->
+        // loop` edge is a forward edge from outside the loop: `loop` is a
+        // dominator-tree *child* of `entry`, visited before `entry` even
+        // finishes, so its phi slot must already exist when `entry`'s
+        // successor fill-in step runs.
+        let src = "@main {\n\
+.entry:\n\
+  i: int = const 0;\n\
+  jmp .loop;\n\
+.loop:\n\
+  c: bool = const true;\n\
+  br c .body .done;\n\
+.body:\n\
+  i: int = const 1;\n\
+  jmp .loop;\n\
+.done:\n\
+  ret;\n\
+}\n";
+        let program = crate::textual::parse_program(src).expect("parse");
+        let ssa = program.functions[0].to_ssa();
+
+        // `c` is defined (and only ever used) entirely within `.loop`, but
+        // this crate's phi placement isn't liveness-pruned, so `.loop` being
+        // in its own dominance frontier (the back edge from `.body`) gives
+        // it a phi too. No definition of `c` reaches `.loop` from `.entry`,
+        // so that phi only has the `.body` incoming edge. It is not what this
+        // test is about — only `i`'s phi is checked.
+        let i_phi = ssa
+            .instrs
+            .iter()
+            .skip_while(|c| !matches!(c, Code::Label { label } if label == "loop"))
+            .take_while(|c| !matches!(c, Code::Label { label } if label == "body"))
+            .find(|c| {
+                matches!(c, Code::Instruction(Instruction::Value { op: ValueOps::Phi, dest: Some(d), .. }) if d.starts_with('i'))
+            })
+            .expect("no phi found for `i` at the loop header");
+
+        match i_phi {
+            Code::Instruction(Instruction::Value { labels, args, .. }) => {
+                let mut labels = labels.clone();
+                labels.sort();
+                assert_eq!(
+                    labels,
+                    vec!["body".to_string(), "entry".to_string()],
+                    "loop header phi is missing an incoming edge"
+                );
+                assert_eq!(args.len(), 2);
+            }
+            other => panic!("expected a phi instruction, got {:?}", other),
+        }
+    }
+}
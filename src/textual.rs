@@ -0,0 +1,707 @@
+//! Parser and pretty-printer for the human-readable Bril surface syntax.
+//!
+//! This gives the crate a second, text-based representation of `Program`
+//! alongside the JSON one already handled by `serde_json`, so a program can
+//! round-trip through either `text -> AST -> text` or `JSON -> AST -> text`.
+//!
+//! Grammar (informally):
+//!
+//! ```text
+//! program  := function*
+//! function := '@' ident ('(' arg (',' arg)* ')')? (':' type)? '{' code* '}'
+//! arg      := ident ':' type
+//! type     := 'int' | 'bool' | 'float' | 'ptr' '<' type '>'
+//! code     := label | const | instr
+//! label    := '.' ident ':'
+//! const    := ident ':' type '=' 'const' literal ';'
+//! instr    := (ident ':' type '=')? op operand* ';'
+//! operand  := ident | '@' ident | '.' ident
+//! literal  := integer | 'true' | 'false' | float | 'NaN' | 'Infinity' | '-Infinity'
+//! ```
+
+use std::fmt;
+
+use crate::object::{
+    Arg, BigIntLiteral, Code, ConstOps, EffectOps, Function, Instruction, Literal, Program, Type,
+    ValueOps,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError(pub String);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "parse error: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// ---------------------------------------------------------------------------
+// Lexer
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Int(i64),
+    Bool(bool),
+    Float(f64),
+    BigInt(String),
+    At,
+    Dot,
+    Colon,
+    Semi,
+    Comma,
+    Eq,
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    Lt,
+    Gt,
+}
+
+/// True if `chars[pos..]` starts with `word` and isn't immediately followed
+/// by another identifier character (so `Infinityx` lexes as one identifier
+/// rather than the `Infinity` keyword plus a stray `x`).
+fn keyword_at(chars: &[char], pos: usize, word: &str) -> bool {
+    let word_chars: Vec<char> = word.chars().collect();
+    if !chars[pos..].starts_with(word_chars.as_slice()) {
+        return false;
+    }
+    match chars.get(pos + word_chars.len()) {
+        Some(c) => !(c.is_alphanumeric() || *c == '_'),
+        None => true,
+    }
+}
+
+fn lex(src: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = vec![];
+    let chars: Vec<char> = src.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if c == '#' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+
+        match c {
+            '@' => {
+                tokens.push(Token::At);
+                i += 1;
+            }
+            '.' => {
+                tokens.push(Token::Dot);
+                i += 1;
+            }
+            ':' => {
+                tokens.push(Token::Colon);
+                i += 1;
+            }
+            ';' => {
+                tokens.push(Token::Semi);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '=' => {
+                tokens.push(Token::Eq);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '{' => {
+                tokens.push(Token::LBrace);
+                i += 1;
+            }
+            '}' => {
+                tokens.push(Token::RBrace);
+                i += 1;
+            }
+            '<' => {
+                tokens.push(Token::Lt);
+                i += 1;
+            }
+            '>' => {
+                tokens.push(Token::Gt);
+                i += 1;
+            }
+            '-' if keyword_at(&chars, i + 1, "Infinity") => {
+                tokens.push(Token::Float(f64::NEG_INFINITY));
+                i += 1 + "Infinity".len();
+            }
+            'I' if keyword_at(&chars, i, "Infinity") => {
+                tokens.push(Token::Float(f64::INFINITY));
+                i += "Infinity".len();
+            }
+            'N' if keyword_at(&chars, i, "NaN") => {
+                tokens.push(Token::Float(f64::NAN));
+                i += "NaN".len();
+            }
+            '-' | '0'..='9' => {
+                let start = i;
+                if c == '-' {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+
+                let mut is_float = false;
+                let next_is_digit = chars.get(i + 1).map(|c| c.is_ascii_digit()).unwrap_or(false);
+                if i < chars.len() && chars[i] == '.' && next_is_digit {
+                    is_float = true;
+                    i += 1;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+
+                if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                    let mut j = i + 1;
+                    if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                        j += 1;
+                    }
+                    let exp_start = j;
+                    while j < chars.len() && chars[j].is_ascii_digit() {
+                        j += 1;
+                    }
+                    if j > exp_start {
+                        is_float = true;
+                        i = j;
+                    }
+                }
+
+                let text: String = chars[start..i].iter().collect();
+                if is_float {
+                    let value = crate::object::float_wire::parse_float(&text)
+                        .map_err(|_| ParseError(format!("invalid float literal `{}`", text)))?;
+                    tokens.push(Token::Float(value));
+                } else if let Ok(value) = text.parse::<i64>() {
+                    tokens.push(Token::Int(value));
+                } else {
+                    tokens.push(Token::BigInt(text));
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                match text.as_str() {
+                    "true" => tokens.push(Token::Bool(true)),
+                    "false" => tokens.push(Token::Bool(false)),
+                    _ => tokens.push(Token::Ident(text)),
+                }
+            }
+            other => {
+                return Err(ParseError(format!("unexpected character `{}`", other)));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+// ---------------------------------------------------------------------------
+// Parser
+// ---------------------------------------------------------------------------
+
+/// An instruction's `(args, funcs, labels)` operand lists, sorted by prefix.
+type Operands = (Vec<String>, Vec<String>, Vec<String>);
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        tok
+    }
+
+    fn expect(&mut self, expected: &Token) -> Result<(), ParseError> {
+        match self.advance() {
+            Some(ref tok) if tok == expected => Ok(()),
+            other => Err(ParseError(format!(
+                "expected {:?}, found {:?}",
+                expected, other
+            ))),
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<String, ParseError> {
+        match self.advance() {
+            Some(Token::Ident(name)) => Ok(name),
+            other => Err(ParseError(format!("expected identifier, found {:?}", other))),
+        }
+    }
+
+    fn parse_program(&mut self) -> Result<Program, ParseError> {
+        let mut functions = vec![];
+        while self.peek().is_some() {
+            functions.push(self.parse_function()?);
+        }
+        Ok(Program { functions })
+    }
+
+    fn parse_function(&mut self) -> Result<Function, ParseError> {
+        self.expect(&Token::At)?;
+        let name = self.expect_ident()?;
+
+        let mut args = vec![];
+        if let Some(Token::LParen) = self.peek() {
+            self.advance();
+            while self.peek() != Some(&Token::RParen) {
+                let arg_name = self.expect_ident()?;
+                self.expect(&Token::Colon)?;
+                let arg_type = self.parse_type()?;
+                args.push(Arg { name: arg_name, arg_type });
+
+                if self.peek() == Some(&Token::Comma) {
+                    self.advance();
+                } else {
+                    break;
+                }
+            }
+            self.expect(&Token::RParen)?;
+        }
+
+        let return_type = if self.peek() == Some(&Token::Colon) {
+            self.advance();
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+
+        self.expect(&Token::LBrace)?;
+        let mut instrs = vec![];
+        while self.peek() != Some(&Token::RBrace) {
+            instrs.push(self.parse_code()?);
+        }
+        self.expect(&Token::RBrace)?;
+
+        Ok(Function { name, args, return_type, instrs })
+    }
+
+    fn parse_type(&mut self) -> Result<Type, ParseError> {
+        let name = self.expect_ident()?;
+        match name.as_str() {
+            "int" => Ok(Type::Int),
+            "bool" => Ok(Type::Bool),
+            "float" => Ok(Type::Float),
+            "ptr" => {
+                self.expect(&Token::Lt)?;
+                let inner = self.parse_type()?;
+                self.expect(&Token::Gt)?;
+                Ok(Type::Ptr(Box::new(inner)))
+            }
+            other => Err(ParseError(format!("unknown type `{}`", other))),
+        }
+    }
+
+    fn parse_code(&mut self) -> Result<Code, ParseError> {
+        if self.peek() == Some(&Token::Dot) {
+            self.advance();
+            let label = self.expect_ident()?;
+            self.expect(&Token::Colon)?;
+            return Ok(Code::Label { label });
+        }
+
+        // Look ahead for `ident ':'`, which marks a value/constant instruction.
+        let has_dest = matches!(self.peek(), Some(Token::Ident(_)))
+            && self.tokens.get(self.pos + 1) == Some(&Token::Colon);
+
+        if has_dest {
+            let dest = self.expect_ident()?;
+            self.expect(&Token::Colon)?;
+            let dest_type = self.parse_type()?;
+            self.expect(&Token::Eq)?;
+            let op_name = self.expect_ident()?;
+
+            if op_name == "const" {
+                let value = self.parse_literal()?;
+                self.expect(&Token::Semi)?;
+                return Ok(Code::Instruction(Instruction::Constant {
+                    op: ConstOps::Const,
+                    dest,
+                    dest_type,
+                    value,
+                }));
+            }
+
+            let op = value_op_from_str(&op_name)?;
+            let (args, funcs, labels) = self.parse_operands()?;
+            self.expect(&Token::Semi)?;
+            return Ok(Code::Instruction(Instruction::Value {
+                op,
+                dest: Some(dest),
+                dest_type: Some(dest_type),
+                args,
+                funcs,
+                labels,
+            }));
+        }
+
+        let op_name = self.expect_ident()?;
+
+        // `call` has no dedicated effect-op variant: a call without a
+        // destination is still a `Value` instruction, just with `dest: None`
+        // (mirroring how the object model represents it).
+        if op_name == "call" {
+            let (args, funcs, labels) = self.parse_operands()?;
+            self.expect(&Token::Semi)?;
+            return Ok(Code::Instruction(Instruction::Value {
+                op: ValueOps::Call,
+                dest: None,
+                dest_type: None,
+                args,
+                funcs,
+                labels,
+            }));
+        }
+
+        let op = effect_op_from_str(&op_name)?;
+        let (args, funcs, labels) = self.parse_operands()?;
+        self.expect(&Token::Semi)?;
+        Ok(Code::Instruction(Instruction::Effect { op, args, funcs, labels }))
+    }
+
+    fn parse_literal(&mut self) -> Result<Literal, ParseError> {
+        match self.advance() {
+            Some(Token::Int(n)) => Ok(Literal::Int(n)),
+            Some(Token::Bool(b)) => Ok(Literal::Bool(b)),
+            Some(Token::Float(f)) => Ok(Literal::Float(f)),
+            Some(Token::BigInt(digits)) => Ok(Literal::BigInt(BigIntLiteral(digits))),
+            other => Err(ParseError(format!("expected a literal, found {:?}", other))),
+        }
+    }
+
+    fn parse_operands(&mut self) -> Result<Operands, ParseError> {
+        let mut args = vec![];
+        let mut funcs = vec![];
+        let mut labels = vec![];
+
+        loop {
+            match self.peek() {
+                Some(Token::At) => {
+                    self.advance();
+                    funcs.push(self.expect_ident()?);
+                }
+                Some(Token::Dot) => {
+                    self.advance();
+                    labels.push(self.expect_ident()?);
+                }
+                Some(Token::Ident(_)) => {
+                    args.push(self.expect_ident()?);
+                }
+                _ => break,
+            }
+        }
+
+        Ok((args, funcs, labels))
+    }
+}
+
+pub fn parse_program(src: &str) -> Result<Program, ParseError> {
+    let tokens = lex(src)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let program = parser.parse_program()?;
+    if parser.peek().is_some() {
+        return Err(ParseError("trailing tokens after last function".into()));
+    }
+    Ok(program)
+}
+
+// ---------------------------------------------------------------------------
+// Pretty-printer
+// ---------------------------------------------------------------------------
+
+fn type_to_str(t: &Type) -> String {
+    match t {
+        Type::Int => "int".to_string(),
+        Type::Bool => "bool".to_string(),
+        Type::Float => "float".to_string(),
+        Type::Ptr(inner) => format!("ptr<{}>", type_to_str(inner)),
+    }
+}
+
+fn value_op_to_str(op: &ValueOps) -> &'static str {
+    match op {
+        ValueOps::Add => "add",
+        ValueOps::Mul => "mul",
+        ValueOps::Sub => "sub",
+        ValueOps::Div => "div",
+        ValueOps::Eq => "eq",
+        ValueOps::Lt => "lt",
+        ValueOps::Gt => "gt",
+        ValueOps::Le => "le",
+        ValueOps::Ge => "ge",
+        ValueOps::Not => "not",
+        ValueOps::And => "and",
+        ValueOps::Or => "or",
+        ValueOps::Call => "call",
+        ValueOps::Id => "id",
+        ValueOps::Alloc => "alloc",
+        ValueOps::Load => "load",
+        ValueOps::PtrAdd => "ptradd",
+        ValueOps::Phi => "phi",
+        ValueOps::Fadd => "fadd",
+        ValueOps::Fmul => "fmul",
+        ValueOps::Fsub => "fsub",
+        ValueOps::Fdiv => "fdiv",
+        ValueOps::Feq => "feq",
+        ValueOps::Flt => "flt",
+        ValueOps::Fle => "fle",
+        ValueOps::Fgt => "fgt",
+        ValueOps::Fge => "fge",
+    }
+}
+
+fn value_op_from_str(s: &str) -> Result<ValueOps, ParseError> {
+    Ok(match s {
+        "add" => ValueOps::Add,
+        "mul" => ValueOps::Mul,
+        "sub" => ValueOps::Sub,
+        "div" => ValueOps::Div,
+        "eq" => ValueOps::Eq,
+        "lt" => ValueOps::Lt,
+        "gt" => ValueOps::Gt,
+        "le" => ValueOps::Le,
+        "ge" => ValueOps::Ge,
+        "not" => ValueOps::Not,
+        "and" => ValueOps::And,
+        "or" => ValueOps::Or,
+        "call" => ValueOps::Call,
+        "id" => ValueOps::Id,
+        "alloc" => ValueOps::Alloc,
+        "load" => ValueOps::Load,
+        "ptradd" => ValueOps::PtrAdd,
+        "phi" => ValueOps::Phi,
+        "fadd" => ValueOps::Fadd,
+        "fmul" => ValueOps::Fmul,
+        "fsub" => ValueOps::Fsub,
+        "fdiv" => ValueOps::Fdiv,
+        "feq" => ValueOps::Feq,
+        "flt" => ValueOps::Flt,
+        "fle" => ValueOps::Fle,
+        "fgt" => ValueOps::Fgt,
+        "fge" => ValueOps::Fge,
+        other => return Err(ParseError(format!("unknown value op `{}`", other))),
+    })
+}
+
+fn effect_op_to_str(op: &EffectOps) -> &'static str {
+    match op {
+        EffectOps::Jmp => "jmp",
+        EffectOps::Br => "br",
+        EffectOps::Ret => "ret",
+        EffectOps::Print => "print",
+        EffectOps::Nop => "nop",
+        EffectOps::Free => "free",
+        EffectOps::Store => "store",
+    }
+}
+
+fn effect_op_from_str(s: &str) -> Result<EffectOps, ParseError> {
+    Ok(match s {
+        "jmp" => EffectOps::Jmp,
+        "br" => EffectOps::Br,
+        "ret" => EffectOps::Ret,
+        "print" => EffectOps::Print,
+        "nop" => EffectOps::Nop,
+        "free" => EffectOps::Free,
+        "store" => EffectOps::Store,
+        // `call` is handled by the caller before reaching here: a `call`
+        // without a destination is still a `Value` instruction (`dest: None`),
+        // never an `EffectOps` variant.
+        other => return Err(ParseError(format!("unknown effect op `{}`", other))),
+    })
+}
+
+fn literal_to_str(l: &Literal) -> String {
+    match l {
+        Literal::Bool(b) => b.to_string(),
+        Literal::Int(n) => n.to_string(),
+        Literal::BigInt(n) => n.0.clone(),
+        // Reuse the JSON wire formatting so a whole-number float like `5.0`
+        // keeps its `.0` (rather than printing as `5`, which would reparse as
+        // `Literal::Int`), and so `NaN`/`Infinity`/`-Infinity` are spelled out.
+        Literal::Float(f) => crate::object::float_wire::format_float(*f),
+    }
+}
+
+fn print_operands(args: &[String], funcs: &[String], labels: &[String]) -> String {
+    // `@func` operands print before plain args so `call` reads the
+    // conventional way (`call @fib n1`, not `call n1 @fib`); every other op
+    // either has no funcs at all or (like `br`'s labels) doesn't care about
+    // the relative order, so this ordering is safe everywhere.
+    let mut parts = vec![];
+    parts.extend(funcs.iter().map(|f| format!("@{}", f)));
+    parts.extend(args.iter().cloned());
+    parts.extend(labels.iter().map(|l| format!(".{}", l)));
+    parts.join(" ")
+}
+
+fn print_code(code: &Code, out: &mut String) {
+    match code {
+        Code::Label { label } => {
+            out.push_str(&format!(".{}:\n", label));
+        }
+        Code::Instruction(Instruction::Constant { dest, dest_type, value, .. }) => {
+            out.push_str(&format!(
+                "  {}: {} = const {};\n",
+                dest,
+                type_to_str(dest_type),
+                literal_to_str(value)
+            ));
+        }
+        Code::Instruction(Instruction::Value { op, dest, dest_type, args, funcs, labels }) => {
+            let operands = print_operands(args, funcs, labels);
+            let prefix = match (dest, dest_type) {
+                (Some(d), Some(t)) => format!("{}: {} = ", d, type_to_str(t)),
+                _ => String::new(),
+            };
+            if operands.is_empty() {
+                out.push_str(&format!("  {}{};\n", prefix, value_op_to_str(op)));
+            } else {
+                out.push_str(&format!("  {}{} {};\n", prefix, value_op_to_str(op), operands));
+            }
+        }
+        Code::Instruction(Instruction::Effect { op, args, funcs, labels }) => {
+            let operands = print_operands(args, funcs, labels);
+            if operands.is_empty() {
+                out.push_str(&format!("  {};\n", effect_op_to_str(op)));
+            } else {
+                out.push_str(&format!("  {} {};\n", effect_op_to_str(op), operands));
+            }
+        }
+    }
+}
+
+fn print_function(f: &Function, out: &mut String) {
+    out.push('@');
+    out.push_str(&f.name);
+
+    if !f.args.is_empty() {
+        out.push('(');
+        let args: Vec<String> = f
+            .args
+            .iter()
+            .map(|a| format!("{}: {}", a.name, type_to_str(&a.arg_type)))
+            .collect();
+        out.push_str(&args.join(", "));
+        out.push(')');
+    }
+
+    if let Some(ref t) = f.return_type {
+        out.push_str(": ");
+        out.push_str(&type_to_str(t));
+    }
+
+    out.push_str(" {\n");
+    for code in &f.instrs {
+        print_code(code, out);
+    }
+    out.push_str("}\n");
+}
+
+pub fn print_program(program: &Program) -> String {
+    let mut out = String::new();
+    for (i, f) in program.functions.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        print_function(f, &mut out);
+    }
+    out
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", print_program(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(src: &str) -> String {
+        let program = parse_program(src).expect("parse");
+        print_program(&program)
+    }
+
+    #[test]
+    fn call_without_dest_round_trips() {
+        let src = "@main(x: int) {\n  call @foo x;\n  ret;\n}\n\n@foo(x: int) {\n  ret;\n}\n";
+        let printed = round_trip(src);
+        assert_eq!(round_trip(&printed), printed);
+        assert!(printed.contains("call @foo x;"));
+    }
+
+    #[test]
+    fn call_prints_callee_before_args() {
+        let src = "@main {\n  r: int = call @fib n1;\n  ret;\n}\n\n@fib(n: int): int {\n  ret n;\n}\n";
+        let printed = round_trip(src);
+        assert!(printed.contains("call @fib n1;"), "got: {}", printed);
+    }
+
+    #[test]
+    fn whole_number_float_round_trips() {
+        let src = "@main {\n  x: float = const 5.0;\n  ret;\n}\n";
+        let printed = round_trip(src);
+        assert!(printed.contains("5.0"), "expected `.0` to survive, got: {}", printed);
+        assert_eq!(round_trip(&printed), printed);
+    }
+
+    #[test]
+    fn special_floats_round_trip() {
+        let src =
+            "@main {\n  a: float = const NaN;\n  b: float = const Infinity;\n  c: float = const -Infinity;\n  ret;\n}\n";
+        let printed = round_trip(src);
+        assert!(printed.contains("NaN"));
+        assert!(printed.contains("Infinity"));
+        assert!(printed.contains("-Infinity"));
+        assert_eq!(round_trip(&printed), printed);
+    }
+
+    #[test]
+    fn exponent_float_round_trips() {
+        let src = "@main {\n  x: float = const 1.5e10;\n  ret;\n}\n";
+        let printed = round_trip(src);
+        let reparsed = parse_program(&printed).expect("reparse");
+        match &reparsed.functions[0].instrs[0] {
+            Code::Instruction(Instruction::Constant { value: Literal::Float(f), .. }) => {
+                assert_eq!(*f, 1.5e10);
+            }
+            other => panic!("expected a float constant, got {:?}", other),
+        }
+    }
+}
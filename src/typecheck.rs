@@ -0,0 +1,390 @@
+//! Static type checker / well-formedness verifier for Bril.
+//!
+//! `Program::typecheck` walks every function before any analysis or
+//! execution runs, tracking a variable -> type environment seeded from
+//! `args` and prior `dest`s, and validating that each instruction's
+//! operands and declared `dest_type` agree with the operation it performs.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::object::{Code, EffectOps, Function, Instruction, Literal, Program, Type, ValueOps};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeError {
+    pub function: String,
+    pub context: String,
+    pub message: String,
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@{} ({}): {}", self.function, self.context, self.message)
+    }
+}
+
+impl std::error::Error for TypeError {}
+
+struct Checker<'a> {
+    program: &'a Program,
+    function: &'a str,
+    env: HashMap<String, Type>,
+    labels: HashSet<&'a str>,
+    errors: Vec<TypeError>,
+}
+
+impl<'a> Checker<'a> {
+    fn error(&mut self, context: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(TypeError {
+            function: self.function.to_string(),
+            context: context.into(),
+            message: message.into(),
+        });
+    }
+
+    fn resolve(&mut self, context: &str, name: &str) -> Option<Type> {
+        match self.env.get(name) {
+            Some(t) => Some(t.clone()),
+            None => {
+                self.error(context, format!("use of undefined variable `{}`", name));
+                None
+            }
+        }
+    }
+
+    fn expect(&mut self, context: &str, name: &str, expected: &Type) -> Option<()> {
+        let actual = self.resolve(context, name)?;
+        if &actual == expected {
+            Some(())
+        } else {
+            self.error(
+                context,
+                format!("expected `{}` to have type {:?}, found {:?}", name, expected, actual),
+            );
+            None
+        }
+    }
+
+    fn expect_ptr(&mut self, context: &str, name: &str) -> Option<Type> {
+        match self.resolve(context, name)? {
+            Type::Ptr(inner) => Some(*inner),
+            other => {
+                self.error(context, format!("expected `{}` to be a pointer, found {:?}", name, other));
+                None
+            }
+        }
+    }
+}
+
+fn literal_type(lit: &Literal) -> Type {
+    match lit {
+        Literal::Int(_) => Type::Int,
+        Literal::Bool(_) => Type::Bool,
+        // A big integer is still conceptually an `int`; only its on-wire
+        // representation differs from a plain `Literal::Int`.
+        Literal::BigInt(_) => Type::Int,
+        Literal::Float(_) => Type::Float,
+    }
+}
+
+fn typecheck_function(program: &Program, func: &'_ Function, errors: &mut Vec<TypeError>) {
+    let labels: HashSet<&str> = func
+        .instrs
+        .iter()
+        .filter_map(|c| match c {
+            Code::Label { label } => Some(label.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut env = HashMap::new();
+    for arg in &func.args {
+        env.insert(arg.name.clone(), arg.arg_type.clone());
+    }
+
+    let mut checker = Checker { program, function: &func.name, env, labels, errors: vec![] };
+
+    for (i, code) in func.instrs.iter().enumerate() {
+        let context = format!("instr {}", i);
+        match code {
+            Code::Label { .. } => {}
+            Code::Instruction(Instruction::Constant { dest, dest_type, value, .. }) => {
+                let actual = literal_type(value);
+                if &actual != dest_type {
+                    checker.error(
+                        &context,
+                        format!("const value has type {:?}, but dest is declared {:?}", actual, dest_type),
+                    );
+                }
+                checker.env.insert(dest.clone(), dest_type.clone());
+            }
+            Code::Instruction(Instruction::Value { op, dest, dest_type, args, funcs, labels: instr_labels }) => {
+                let dest_decl = (dest, dest_type);
+                typecheck_value(&mut checker, &context, op, dest_decl, args, funcs, instr_labels);
+            }
+            Code::Instruction(Instruction::Effect { op, args, labels: instr_labels, .. }) => {
+                typecheck_effect(&mut checker, &context, func, op, args, instr_labels);
+            }
+        }
+    }
+
+    errors.append(&mut checker.errors);
+}
+
+fn typecheck_value(
+    checker: &mut Checker,
+    context: &str,
+    op: &ValueOps,
+    dest_decl: (&Option<String>, &Option<Type>),
+    args: &[String],
+    funcs: &[String],
+    _labels: &[String],
+) {
+    let (dest, dest_type) = dest_decl;
+    let result_type = match op {
+        ValueOps::Add | ValueOps::Mul | ValueOps::Sub | ValueOps::Div => {
+            for a in args {
+                checker.expect(context, a, &Type::Int);
+            }
+            Some(Type::Int)
+        }
+        ValueOps::Eq | ValueOps::Lt | ValueOps::Gt | ValueOps::Le | ValueOps::Ge => {
+            for a in args {
+                checker.expect(context, a, &Type::Int);
+            }
+            Some(Type::Bool)
+        }
+        ValueOps::Fadd | ValueOps::Fmul | ValueOps::Fsub | ValueOps::Fdiv => {
+            for a in args {
+                checker.expect(context, a, &Type::Float);
+            }
+            Some(Type::Float)
+        }
+        ValueOps::Feq | ValueOps::Flt | ValueOps::Fle | ValueOps::Fgt | ValueOps::Fge => {
+            for a in args {
+                checker.expect(context, a, &Type::Float);
+            }
+            Some(Type::Bool)
+        }
+        ValueOps::Not => {
+            if let Some(a) = args.first() {
+                checker.expect(context, a, &Type::Bool);
+            }
+            Some(Type::Bool)
+        }
+        ValueOps::And | ValueOps::Or => {
+            for a in args {
+                checker.expect(context, a, &Type::Bool);
+            }
+            Some(Type::Bool)
+        }
+        ValueOps::Id => args.first().and_then(|a| checker.resolve(context, a)),
+        ValueOps::Alloc => {
+            if let Some(a) = args.first() {
+                checker.expect(context, a, &Type::Int);
+            }
+            // `alloc` always produces a pointer; there's no argument to infer
+            // the pointee type from, so the best we can check is that the
+            // declared dest type is a `Ptr` at all (rather than trusting it
+            // unconditionally, which would let e.g. `x: bool = alloc n;`
+            // typecheck).
+            match dest_type {
+                Some(Type::Ptr(_)) => dest_type.clone(),
+                Some(other) => {
+                    checker.error(context, format!("`alloc` produces a pointer, but dest is declared {:?}", other));
+                    None
+                }
+                None => None,
+            }
+        }
+        ValueOps::Load => args.first().and_then(|a| checker.expect_ptr(context, a)),
+        ValueOps::PtrAdd => {
+            let inner = args.first().and_then(|a| checker.expect_ptr(context, a));
+            if let Some(b) = args.get(1) {
+                checker.expect(context, b, &Type::Int);
+            }
+            inner.map(|t| Type::Ptr(Box::new(t)))
+        }
+        ValueOps::Call => {
+            typecheck_call(checker, context, funcs, args);
+            let callee = funcs.first().and_then(|name| {
+                checker.program.functions.iter().find(|f| &f.name == name)
+            });
+            callee.and_then(|f| f.return_type.clone())
+        }
+        ValueOps::Phi => {
+            if args.len() != _labels.len() {
+                checker.error(
+                    context,
+                    format!("phi has {} value(s) but {} label(s)", args.len(), _labels.len()),
+                );
+            }
+            for label in _labels {
+                if !checker.labels.contains(label.as_str()) {
+                    checker.error(context, format!("phi references undefined label `.{}`", label));
+                }
+            }
+
+            let mut types = args.iter().filter_map(|a| checker.resolve(context, a));
+            let first = types.next();
+            if let Some(ref t) = first {
+                for other in types {
+                    if &other != t {
+                        checker.error(context, "phi arguments have mismatched types");
+                        break;
+                    }
+                }
+            }
+            first
+        }
+    };
+
+    if let (Some(dest), Some(declared)) = (dest, dest_type) {
+        match result_type {
+            Some(ref actual) if actual != declared => checker.error(
+                context,
+                format!("`{}` declared as {:?}, but op produces {:?}", dest, declared, actual),
+            ),
+            Some(_) => {}
+            None => {}
+        }
+        checker.env.insert(dest.clone(), declared.clone());
+    }
+}
+
+fn typecheck_call(checker: &mut Checker, context: &str, funcs: &[String], args: &[String]) {
+    let name = match funcs.first() {
+        Some(name) => name,
+        None => {
+            checker.error(context, "call requires a target function");
+            return;
+        }
+    };
+
+    let target = match checker.program.functions.iter().find(|f| &f.name == name) {
+        Some(f) => f,
+        None => {
+            checker.error(context, format!("call to undefined function `@{}`", name));
+            return;
+        }
+    };
+
+    if target.args.len() != args.len() {
+        checker.error(
+            context,
+            format!(
+                "`@{}` expects {} argument(s), found {}",
+                name,
+                target.args.len(),
+                args.len()
+            ),
+        );
+        return;
+    }
+
+    for (arg_decl, actual_name) in target.args.iter().zip(args.iter()) {
+        checker.expect(context, actual_name, &arg_decl.arg_type);
+    }
+}
+
+fn typecheck_effect(
+    checker: &mut Checker,
+    context: &str,
+    func: &Function,
+    op: &EffectOps,
+    args: &[String],
+    labels: &[String],
+) {
+    match op {
+        EffectOps::Jmp => {
+            check_label(checker, context, labels.first());
+        }
+        EffectOps::Br => {
+            if let Some(cond) = args.first() {
+                checker.expect(context, cond, &Type::Bool);
+            } else {
+                checker.error(context, "br requires a condition operand");
+            }
+            check_label(checker, context, labels.first());
+            check_label(checker, context, labels.get(1));
+        }
+        EffectOps::Ret => match &func.return_type {
+            Some(t) => match args.first() {
+                Some(name) => {
+                    checker.expect(context, name, t);
+                }
+                None => checker.error(context, format!("function returns {:?}, but `ret` has no value", t)),
+            },
+            None => {
+                if !args.is_empty() {
+                    checker.error(context, "function has no return type, but `ret` carries a value");
+                }
+            }
+        },
+        EffectOps::Print => {
+            for a in args {
+                checker.resolve(context, a);
+            }
+        }
+        EffectOps::Nop => {}
+        EffectOps::Free => {
+            if let Some(a) = args.first() {
+                checker.expect_ptr(context, a);
+            } else {
+                checker.error(context, "free requires a pointer operand");
+            }
+        }
+        EffectOps::Store => {
+            let inner = args.first().and_then(|a| checker.expect_ptr(context, a));
+            if let (Some(inner), Some(value)) = (inner, args.get(1)) {
+                checker.expect(context, value, &inner);
+            } else if args.get(1).is_none() {
+                checker.error(context, "store requires a pointer and a value operand");
+            }
+        }
+    }
+}
+
+fn check_label(checker: &mut Checker, context: &str, label: Option<&String>) {
+    match label {
+        Some(l) if checker.labels.contains(l.as_str()) => {}
+        Some(l) => checker.error(context, format!("reference to undefined label `.{}`", l)),
+        None => checker.error(context, "missing label operand"),
+    }
+}
+
+impl Program {
+    /// Validates every function in the program, returning all well-formedness
+    /// violations found rather than stopping at the first one.
+    pub fn typecheck(&self) -> Result<(), Vec<TypeError>> {
+        let mut errors = vec![];
+        for func in &self.functions {
+            typecheck_function(self, func, &mut errors);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::textual::parse_program;
+
+    #[test]
+    fn alloc_rejects_a_non_pointer_dest_type() {
+        let program = parse_program("@main {\n  n: int = const 1;\n  x: bool = alloc n;\n  ret;\n}\n")
+            .expect("parse");
+        assert!(program.typecheck().is_err());
+    }
+
+    #[test]
+    fn alloc_accepts_a_pointer_dest_type() {
+        let program = parse_program("@main {\n  n: int = const 1;\n  p: ptr<int> = alloc n;\n  ret;\n}\n")
+            .expect("parse");
+        assert_eq!(program.typecheck(), Ok(()));
+    }
+}